@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(target_arch = "wasm32")]
+use bevy::asset::io::{
+    memory::{Dir, MemoryAssetReader},
+    AssetSource, AssetSourceId,
+};
+#[cfg(target_arch = "wasm32")]
+use bevy::prelude::App;
+
+/// Resolve a path under `assets/`; canonicalized on native. On wasm32 there is no
+/// filesystem, so this points at the `embedded://` source `register_embedded_assets`
+/// registers, which serves `EMBEDDED_ASSETS` straight out of the binary instead of
+/// fetching `assets/` over HTTP.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resolve(relative: impl AsRef<Path>) -> PathBuf {
+    PathBuf::from("./assets")
+        .join(relative)
+        .canonicalize()
+        .unwrap()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn resolve(relative: impl AsRef<Path>) -> PathBuf {
+    PathBuf::from("embedded://").join(relative)
+}
+
+/// Every asset this example loads, baked into the wasm32 binary with `include_bytes!`.
+/// Add an entry here whenever a new asset path is introduced.
+#[cfg(target_arch = "wasm32")]
+const EMBEDDED_ASSETS: &[(&str, &[u8])] = &[
+    (
+        "sprite/racing/car_red.png",
+        include_bytes!("../assets/sprite/racing/car_red.png"),
+    ),
+    (
+        "sprite/racing/car_yellow.png",
+        include_bytes!("../assets/sprite/racing/car_yellow.png"),
+    ),
+    (
+        "sprite/racing/barrel_red.png",
+        include_bytes!("../assets/sprite/racing/barrel_red.png"),
+    ),
+    (
+        "sprite/racing/cone_striped.png",
+        include_bytes!("../assets/sprite/racing/cone_striped.png"),
+    ),
+];
+
+/// Serve `EMBEDDED_ASSETS` under the `embedded://` scheme `resolve()` returns, so
+/// Bevy's asset server reads the bundled bytes instead of issuing an HTTP request.
+///
+/// Bevy only honors a custom asset source if it's registered before `AssetPlugin`
+/// finishes building, so call this as early as possible - right after `Game::new()`,
+/// before `window_settings`/`add_sprite`/`run`. Whether that is early enough depends on
+/// when `Game::new()` finishes building its internal `DefaultPlugins`, which isn't
+/// pinned to a `rusty_engine`/bevy version anywhere in this tree; if it turns out
+/// `Game::new()` already finishes `AssetPlugin` before returning, this needs an
+/// upstream `rusty_engine` hook that exposes a raw `App` prior to that point.
+#[cfg(target_arch = "wasm32")]
+pub fn register_embedded_assets(app: &mut App) {
+    let dir = Dir::default();
+    for (path, bytes) in EMBEDDED_ASSETS {
+        dir.insert_asset(Path::new(path), bytes.to_vec());
+    }
+    app.register_asset_source(
+        AssetSourceId::from("embedded"),
+        AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+    );
+}
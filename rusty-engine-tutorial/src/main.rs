@@ -1,12 +1,62 @@
-use std::path::PathBuf;
-
+use log::{debug, info, trace, warn};
+use rand::{rng, Rng};
 use rusty_engine::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+mod assets;
+mod save_profile;
+use save_profile::SaveProfile;
+
+const DEFAULT_ENEMY_DAMAGE: f32 = 25.0;
+// Obstacles deal different amounts of damage depending on what the player hits.
+const OBSTACLES: [(&str, f32); 2] = [
+    ("sprite/racing/barrel_red.png", DEFAULT_ENEMY_DAMAGE),
+    ("sprite/racing/cone_striped.png", 10.0),
+];
+const ACCEL: f32 = 600.0;
+const DRAG: f32 = 1.5;
+const MAX_SPEED: f32 = 300.0;
+const VELOCITY_EPSILON: f32 = 1.0;
+
+// A boarded vehicle accelerates harder, coasts longer and tops out faster than the player on foot.
+const VEHICLE_ACCEL: f32 = 900.0;
+const VEHICLE_DRAG: f32 = 1.0;
+const VEHICLE_MAX_SPEED: f32 = 450.0;
+const BOARD_RANGE: f32 = 80.0;
+
+/// Fired when the player boards or leaves a vehicle.
+enum VehicleEnterExit {
+    Board(String),
+    Exit,
+}
+
+struct HitPoints {
+    current: f32,
+    max: f32,
+}
+
+impl Default for HitPoints {
+    fn default() -> Self {
+        Self {
+            current: 100.0,
+            max: 100.0,
+        }
+    }
+}
 
 #[derive(Resource)]
 struct GameState {
     high_score: u32,
     current_score: u32,
     enemy_label: Vec<String>,
+    enemy_damage: Vec<f32>,
+    obstacle_index: i32,
+    health: HitPoints,
+    game_over: bool,
+    velocity: Vec2,
+    acceleration: Vec2,
+    inside_entity: Option<String>,
     spawn_timer: Timer,
 }
 
@@ -16,24 +66,60 @@ impl Default for GameState {
             high_score: 0,
             current_score: 0,
             enemy_label: Vec::new(),
-            spawn_timer: Timer::from_seconds(10.0, TimerMode::Once),
+            enemy_damage: Vec::new(),
+            obstacle_index: 0,
+            health: HitPoints::default(),
+            game_over: false,
+            velocity: Vec2::ZERO,
+            acceleration: Vec2::ZERO,
+            inside_entity: None,
+            spawn_timer: Timer::from_seconds(10.0, TimerMode::Repeating),
         }
     }
 }
 
+// NOTE: wasm32 also needs `crate-type = ["cdylib", "rlib"]` and a portable `delta_f32`/
+// `time_since_startup_f64` source; out of scope here (no Cargo.toml checked in). Whether
+// `assets::register_embedded_assets` runs early enough also depends on when `Game::new()`
+// finishes building `AssetPlugin` internally, which can't be confirmed without a pinned
+// `rusty_engine`/bevy version - so end-to-end wasm32 buildability is unverified, not delivered.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+pub fn run() {
+    start_game();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    start_game();
+}
+
+// `pretty_env_logger` reads the environment and writes to a terminal, neither of which exist
+// on wasm32; log there through `console_log` instead so the wasm entry point stays usable.
+#[cfg(not(target_arch = "wasm32"))]
+fn init_logger() {
+    pretty_env_logger::init();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn init_logger() {
+    console_log::init_with_level(log::Level::Debug).expect("console_log::init_with_level failed");
+}
+
+fn start_game() {
+    init_logger();
+
     // Initialize the engine
     let mut game = Game::new();
+    let profile = save_profile::load();
+
+    #[cfg(target_arch = "wasm32")]
+    assets::register_embedded_assets(&mut game.app);
 
     // Setup game
-    let assets = PathBuf::from("./assets");
-    let sprites_folder= assets.join("sprite");
-    let audio_folder = assets.join("audio");
-    let fonts_folder = assets.join("fonts");
-    let racing_assets = sprites_folder.join("racing");
+    let racing_assets = "sprite/racing";
 
     // Player
-    let player_car = racing_assets.join("car_red.png").canonicalize().unwrap();
+    let player_car = assets::resolve(format!("{racing_assets}/car_red.png"));
 
     let player = game.add_sprite("player", player_car);
     player.translation = Vec2::new(0.0, 0.0);
@@ -41,37 +127,254 @@ fn main() {
     player.scale = 1.0;
     player.collision = true;
 
-    // Car
-    let car1 = racing_assets.join("car_yellow.png").canonicalize().unwrap();
+    // Car - a boardable vehicle, not a disposable enemy, so it starts out of collision
+    // detection; boarding range is checked by distance instead (see `game_logic`). Its
+    // collider is switched back on for as long as the player is piloting it, so a driven
+    // car can still take (and deal) collision-based hits.
+    let car1 = assets::resolve(format!("{racing_assets}/car_yellow.png"));
     let car1 = game.add_sprite("car1", car1);
     car1.translation = Vec2::new(300.0, 0.0);
-    car1.collision = true;
+    car1.collision = false;
+    debug!(target: "game::spawn", "Spawned car1 at {:?}", car1.translation);
+
+    // HUD
+    let score = game.add_text("score", "Score: 0");
+    score.translation = Vec2::new(520.0, 320.0);
+
+    let health = game.add_text("health", "HP: 100/100");
+    health.translation = Vec2::new(-520.0, 320.0);
+
+    let high_score = game.add_text("high_score", format!("High Score: {}", profile.high_score));
+    high_score.translation = Vec2::new(-520.0, 280.0);
+
+    let game_over = game.add_text("game_over", "");
+    game_over.translation = Vec2::new(0.0, 0.0);
 
     // Game Logic
     game.add_logic(game_logic);
 
     // Start the game loop
-    game.run(GameState::default());
+    game.run(GameState {
+        high_score: profile.high_score,
+        ..GameState::default()
+    });
 }
 
 fn game_logic(engine: &mut Engine, state: &mut GameState) {
     engine.show_colliders = true;
+
+    if state.game_over {
+        if engine.keyboard_state.just_pressed(KeyCode::R) {
+            state.health = HitPoints::default();
+            state.current_score = 0;
+            state.game_over = false;
+            state.inside_entity = None;
+            state.velocity = Vec2::ZERO;
+
+            // Clear out every obstacle that survived the player's death so it can't hit
+            // the respawned player again, and so the sprite map/vectors don't grow
+            // unbounded across repeated restarts.
+            for label in state.enemy_label.drain(..) {
+                engine.sprites.remove(&label);
+            }
+            state.enemy_damage.clear();
+
+            // Always restart on foot, outside the car, regardless of what was piloted
+            // when the game ended.
+            let player = engine.sprites.get_mut("player").unwrap();
+            player.translation = Vec2::new(0.0, 0.0);
+            player.scale = 1.0;
+            player.collision = true;
+
+            let car1 = engine.sprites.get_mut("car1").unwrap();
+            car1.translation = Vec2::new(300.0, 0.0);
+            car1.collision = false;
+
+            let score_text = engine.texts.get_mut("score").unwrap();
+            score_text.value = format!("Score: {}", state.current_score);
+            let game_over_text = engine.texts.get_mut("game_over").unwrap();
+            game_over_text.value = String::new();
+        }
+        return;
+    }
+
+    // The vehicle the player is currently piloting takes collision credit (and damage)
+    // in place of the player sprite.
+    let piloted = state.inside_entity.clone().unwrap_or_else(|| "player".to_string());
+
     for event in engine.collision_events.drain(..) {
-        if event.state == CollisionState::Begin && event.pair.one_starts_with("player") {
-            // Remove the sprite that player collided with
+        if event.state == CollisionState::Begin && event.pair.one_starts_with(&piloted) {
+            // Figure out how much damage the enemy we hit deals
+            let damage = [&event.pair.0, &event.pair.1]
+                .into_iter()
+                .find(|label| **label != piloted)
+                .and_then(|label| state.enemy_label.iter().position(|l| l == label))
+                .map(|i| state.enemy_damage[i])
+                .unwrap_or(DEFAULT_ENEMY_DAMAGE);
+
+            // Remove the sprite that the piloted entity collided with
             for label in [event.pair.0, event.pair.1] {
-                if label != "player" {
-                    engine.sprites.remove(&label);
+                if label != piloted {
+                    if engine.sprites.remove(&label).is_none() {
+                        warn!(target: "game::collision", "Collision with '{label}' dropped: sprite was already removed");
+                    }
+                    if let Some(i) = state.enemy_label.iter().position(|l| *l == label) {
+                        state.enemy_label.remove(i);
+                        state.enemy_damage.remove(i);
+                    }
+                }
+            }
+
+            engine.audio_manager.play_sfx(SfxPreset::Impact2, 0.5);
+            state.health.current = (state.health.current - damage).max(0.0);
+
+            state.current_score += 1;
+            info!(target: "game::score", "Current Score: {}", state.current_score);
+            let score_text = engine.texts.get_mut("score").unwrap();
+            score_text.value = format!("Score: {}", state.current_score);
+
+            if state.current_score > state.high_score {
+                state.high_score = state.current_score;
+                info!(target: "game::score", "New high score: {}", state.high_score);
+                let profile = SaveProfile {
+                    high_score: state.high_score,
+                };
+                if let Err(err) = save_profile::save(&profile) {
+                    warn!(target: "game::save", "Failed to save high score: {err}");
+                }
+                let high_score_text = engine.texts.get_mut("high_score").unwrap();
+                high_score_text.value = format!("High Score: {}", state.high_score);
+            }
+
+            if state.health.current <= 0.0 {
+                state.game_over = true;
+                let game_over_text = engine.texts.get_mut("game_over").unwrap();
+                game_over_text.value = "GAME OVER - Press R to restart".to_string();
+            }
+        }
+    }
+
+    let health_text = engine.texts.get_mut("health").unwrap();
+    health_text.value = format!("HP: {:.0}/{:.0}", state.health.current, state.health.max);
+
+    // Board or exit a vehicle on E
+    if engine.keyboard_state.just_pressed(KeyCode::E) {
+        let enter_exit = match &state.inside_entity {
+            Some(_) => Some(VehicleEnterExit::Exit),
+            None => {
+                let player_pos = engine.sprites.get("player").unwrap().translation;
+                let car_pos = engine.sprites.get("car1").unwrap().translation;
+                (player_pos.distance(car_pos) <= BOARD_RANGE)
+                    .then(|| VehicleEnterExit::Board("car1".to_string()))
+            }
+        };
+
+        match enter_exit {
+            Some(VehicleEnterExit::Board(vehicle_label)) => {
+                info!(target: "game::input", "Player boarded '{vehicle_label}'");
+                let player = engine.sprites.get_mut("player").unwrap();
+                player.scale = 0.0;
+                player.collision = false;
+                let vehicle = engine.sprites.get_mut(&vehicle_label).unwrap();
+                vehicle.collision = true;
+                state.velocity = Vec2::ZERO;
+                state.inside_entity = Some(vehicle_label);
+            }
+            Some(VehicleEnterExit::Exit) => {
+                if let Some(vehicle_label) = state.inside_entity.take() {
+                    let vehicle = engine.sprites.get_mut(&vehicle_label).unwrap();
+                    vehicle.collision = false;
+                    let vehicle_pos = vehicle.translation;
+                    let player = engine.sprites.get_mut("player").unwrap();
+                    player.translation = vehicle_pos + Vec2::new(0.0, 80.0);
+                    player.scale = 1.0;
+                    player.collision = true;
+                    state.velocity = Vec2::ZERO;
+                    info!(target: "game::input", "Player exited '{vehicle_label}'");
                 }
             }
+            None => {}
         }
+    }
 
-        // println!("Collision detected: {:#?}", event);
-        state.current_score += 1;
-        println!("Current Score: {}", state.current_score);
+    let piloted = state.inside_entity.clone().unwrap_or_else(|| "player".to_string());
+    let (accel, drag, max_speed) = if state.inside_entity.is_some() {
+        (VEHICLE_ACCEL, VEHICLE_DRAG, VEHICLE_MAX_SPEED)
+    } else {
+        (ACCEL, DRAG, MAX_SPEED)
+    };
+
+    // Read input into an acceleration vector
+    state.acceleration = Vec2::ZERO;
+    if engine
+        .keyboard_state
+        .pressed_any(&[KeyCode::Up, KeyCode::W])
+    {
+        state.acceleration.y += accel;
+    };
+    if engine
+        .keyboard_state
+        .pressed_any(&[KeyCode::Down, KeyCode::S])
+    {
+        state.acceleration.y -= accel;
+    };
+    if engine
+        .keyboard_state
+        .pressed_any(&[KeyCode::Left, KeyCode::A])
+    {
+        state.acceleration.x -= accel;
+    };
+    if engine
+        .keyboard_state
+        .pressed_any(&[KeyCode::Right, KeyCode::D])
+    {
+        state.acceleration.x += accel;
+    };
+
+    // Integrate velocity, apply drag, and clamp to top speed
+    state.velocity += state.acceleration * engine.delta_f32;
+    state.velocity *= (1.0 - drag * engine.delta_f32).max(0.0);
+    if state.velocity.length() > max_speed {
+        state.velocity = state.velocity.normalize() * max_speed;
+    }
+    if state.velocity.length() < VELOCITY_EPSILON {
+        state.velocity = Vec2::ZERO;
+    }
+
+    let piloted_sprite = engine.sprites.get_mut(&piloted).unwrap();
+    piloted_sprite.translation += state.velocity * engine.delta_f32;
+    if state.velocity != Vec2::ZERO {
+        piloted_sprite.rotation = state.velocity.y.atan2(state.velocity.x);
     }
+    trace!(target: "game::input", "{piloted} position: {:?}", piloted_sprite.translation);
 
+    // Spawn an obstacle at the mouse for whatever's piloted (player or a boarded vehicle)
+    // to hit; this and the spawn timer below are what `car1`'s collider can detect.
+    if engine.mouse_state.just_pressed(MouseButton::Left) {
+        if let Some(mouse_location) = engine.mouse_state.location() {
+            let (asset, damage) = OBSTACLES[rng().random_range(0..OBSTACLES.len())];
+            let label = format!("obstacle_{}", state.obstacle_index);
+            state.obstacle_index += 1;
+            let obstacle = engine.add_sprite(label.clone(), assets::resolve(asset));
+            obstacle.translation = mouse_location;
+            obstacle.collision = true;
+            debug!(target: "game::spawn", "Spawned '{label}' at {:?}", obstacle.translation);
+            state.enemy_label.push(label);
+            state.enemy_damage.push(damage);
+        }
+    }
 
-    let player = engine.sprites.get_mut("player").unwrap();
-    player.translation.x += 100.0 * engine.delta_f32;
+    // Timer for spawning obstacles at random positions
+    if state.spawn_timer.tick(engine.delta).just_finished() {
+        let (asset, damage) = OBSTACLES[rng().random_range(0..OBSTACLES.len())];
+        let label = format!("obstacle_{}", state.obstacle_index);
+        state.obstacle_index += 1;
+        let obstacle = engine.add_sprite(label.clone(), assets::resolve(asset));
+        obstacle.translation.x = rng().random_range(-550.0..550.0);
+        obstacle.translation.y = rng().random_range(-325.0..325.0);
+        obstacle.collision = true;
+        debug!(target: "game::spawn", "Spawned '{label}' at {:?}", obstacle.translation);
+        state.enemy_label.push(label);
+        state.enemy_damage.push(damage);
+    }
 }
@@ -1,16 +1,44 @@
-use std::path::PathBuf;
-use std::sync::LazyLock;
-
+use log::{debug, info, trace, warn};
 use rand::{rng, Rng};
 use rusty_engine::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+mod assets;
+mod save_profile;
+use save_profile::SaveProfile;
+
+const DEFAULT_ENEMY_DAMAGE: f32 = 10.0;
+const ACCEL: f32 = 600.0;
+const DRAG: f32 = 1.5;
+const MAX_SPEED: f32 = 300.0;
+const VELOCITY_EPSILON: f32 = 1.0;
 
-const ASSETS: LazyLock<PathBuf> = LazyLock::new(|| PathBuf::from("./assets"));
+struct HitPoints {
+    current: f32,
+    max: f32,
+}
+
+impl Default for HitPoints {
+    fn default() -> Self {
+        Self {
+            current: 100.0,
+            max: 100.0,
+        }
+    }
+}
 
 #[derive(Resource)]
 struct GameState {
     high_score: u32,
     score: u32,
     ferris_index: i32,
+    enemy_label: Vec<String>,
+    enemy_damage: Vec<f32>,
+    health: HitPoints,
+    game_over: bool,
+    velocity: Vec2,
+    acceleration: Vec2,
     spawn_timer: Timer,
 }
 
@@ -20,14 +48,53 @@ impl Default for GameState {
             high_score: 0,
             score: 0,
             ferris_index: 0,
+            enemy_label: Vec::new(),
+            enemy_damage: Vec::new(),
+            health: HitPoints::default(),
+            game_over: false,
+            velocity: Vec2::ZERO,
+            acceleration: Vec2::ZERO,
             spawn_timer: Timer::from_seconds(2.0, TimerMode::Repeating),
         }
     }
 }
 
+// NOTE: wasm32 also needs `crate-type = ["cdylib", "rlib"]` and a portable `delta_f32`/
+// `time_since_startup_f64` source; out of scope here (no Cargo.toml checked in). Whether
+// `assets::register_embedded_assets` runs early enough also depends on when `Game::new()`
+// finishes building `AssetPlugin` internally, which can't be confirmed without a pinned
+// `rusty_engine`/bevy version - so end-to-end wasm32 buildability is unverified, not delivered.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+pub fn run() {
+    start_game();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    start_game();
+}
+
+// `pretty_env_logger` reads the environment and writes to a terminal, neither of which exist
+// on wasm32; log there through `console_log` instead so the wasm entry point stays usable.
+#[cfg(not(target_arch = "wasm32"))]
+fn init_logger() {
+    pretty_env_logger::init();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn init_logger() {
+    console_log::init_with_level(log::Level::Debug).expect("console_log::init_with_level failed");
+}
+
+fn start_game() {
+    init_logger();
+
     // Initialize the engine
     let mut game = Game::new();
+    let profile = save_profile::load();
+
+    #[cfg(target_arch = "wasm32")]
+    assets::register_embedded_assets(&mut game.app);
 
     game.window_settings(Window {
         title: "Tutorial!".to_string(),
@@ -45,14 +112,23 @@ fn main() {
     let score = game.add_text("score", "Score: 0");
     score.translation = Vec2::new(520.0, 320.0);
 
-    let high_score = game.add_text("high_score", "High Score: 0");
+    let high_score = game.add_text("high_score", format!("High Score: {}", profile.high_score));
     high_score.translation = Vec2::new(-520.0, 320.0);
 
+    let health = game.add_text("health", "HP: 100/100");
+    health.translation = Vec2::new(-520.0, 280.0);
+
+    let game_over = game.add_text("game_over", "");
+    game_over.translation = Vec2::new(0.0, 0.0);
+
     // Game Logic
     game.add_logic(game_logic);
 
     // Start the game loop
-    game.run(GameState::default());
+    game.run(GameState {
+        high_score: profile.high_score,
+        ..GameState::default()
+    });
 }
 
 fn game_logic(engine: &mut Engine, game_state: &mut GameState) {
@@ -72,68 +148,145 @@ fn game_logic(engine: &mut Engine, game_state: &mut GameState) {
     high_score.translation.x = -engine.window_dimensions.x / 2.0 + 100.0;
     high_score.translation.y = engine.window_dimensions.y / 2.0 - 30.0;
 
+    let health = engine.texts.get_mut("health").unwrap();
+    health.translation.x = -engine.window_dimensions.x / 2.0 + 100.0;
+    health.translation.y = engine.window_dimensions.y / 2.0 - 60.0;
+    health.value = format!(
+        "HP: {:.0}/{:.0}",
+        game_state.health.current, game_state.health.max
+    );
+
+    // Game over: freeze movement and wait for a restart
+    if game_state.game_over {
+        if engine.keyboard_state.just_pressed(KeyCode::R) {
+            game_state.health = HitPoints::default();
+            game_state.score = 0;
+            game_state.game_over = false;
+
+            // Clear out every enemy sprite that survived the player's death so it can't
+            // hit the respawned player again, and so the sprite map/vectors don't grow
+            // unbounded across repeated restarts.
+            for label in game_state.enemy_label.drain(..) {
+                engine.sprites.remove(&label);
+            }
+            game_state.enemy_damage.clear();
+
+            let score = engine.texts.get_mut("score").unwrap();
+            score.value = format!("Score: {}", game_state.score);
+            let game_over = engine.texts.get_mut("game_over").unwrap();
+            game_over.value = String::new();
+        }
+        return;
+    }
+
     // Handle Collision Events
     for event in engine.collision_events.drain(..) {
         if event.state == CollisionState::Begin && event.pair.one_starts_with("player") {
+            // Figure out how much damage the enemy we hit deals
+            let damage = [&event.pair.0, &event.pair.1]
+                .into_iter()
+                .find(|label| *label != "player")
+                .and_then(|label| game_state.enemy_label.iter().position(|l| l == label))
+                .map(|i| game_state.enemy_damage[i])
+                .unwrap_or(DEFAULT_ENEMY_DAMAGE);
+
             // Remove the sprite that player collided with
             for label in [event.pair.0, event.pair.1] {
                 if label != "player" {
-                    engine.sprites.remove(&label);
+                    if engine.sprites.remove(&label).is_none() {
+                        warn!(target: "game::collision", "Collision with '{label}' dropped: sprite was already removed");
+                    }
+                    if let Some(i) = game_state.enemy_label.iter().position(|l| *l == label) {
+                        game_state.enemy_label.remove(i);
+                        game_state.enemy_damage.remove(i);
+                    }
                 }
-                // println!("Collision detected: {:#?}", event);
             }
             game_state.score += 1;
+            info!(target: "game::score", "Score: {}", game_state.score);
             let score = engine.texts.get_mut("score").unwrap();
             score.value = format!("Score: {}", game_state.score);
 
             if game_state.score > game_state.high_score {
                 game_state.high_score = game_state.score;
+                info!(target: "game::score", "New high score: {}", game_state.high_score);
+                let profile = SaveProfile {
+                    high_score: game_state.high_score,
+                };
+                if let Err(err) = save_profile::save(&profile) {
+                    warn!(target: "game::save", "Failed to save high score: {err}");
+                }
             }
             let high_score = engine.texts.get_mut("high_score").unwrap();
             high_score.value = format!("High Score: {}", game_state.high_score);
-            engine.audio_manager.play_sfx(SfxPreset::Minimize1, 0.5);
+
+            engine.audio_manager.play_sfx(SfxPreset::Impact2, 0.5);
+            game_state.health.current = (game_state.health.current - damage).max(0.0);
+            if game_state.health.current <= 0.0 {
+                game_state.game_over = true;
+                let game_over = engine.texts.get_mut("game_over").unwrap();
+                game_over.value = "GAME OVER - Press R to restart".to_string();
+            }
         }
     }
 
     // Handle Movement with Input
-    let player = engine.sprites.get_mut("player").unwrap();
-
-    const MOVEMENT_SPEED: f32 = 100.0;
+    game_state.acceleration = Vec2::ZERO;
     if engine
         .keyboard_state
         .pressed_any(&[KeyCode::Up, KeyCode::W])
     {
-        player.translation.y += MOVEMENT_SPEED * engine.delta_f32;
+        game_state.acceleration.y += ACCEL;
     };
     if engine
         .keyboard_state
         .pressed_any(&[KeyCode::Down, KeyCode::S])
     {
-        player.translation.y -= MOVEMENT_SPEED * engine.delta_f32;
+        game_state.acceleration.y -= ACCEL;
     };
     if engine
         .keyboard_state
         .pressed_any(&[KeyCode::Left, KeyCode::A])
     {
-        player.translation.x -= MOVEMENT_SPEED * engine.delta_f32;
+        game_state.acceleration.x -= ACCEL;
     };
     if engine
         .keyboard_state
         .pressed_any(&[KeyCode::Right, KeyCode::D])
     {
-        player.translation.x += MOVEMENT_SPEED * engine.delta_f32;
+        game_state.acceleration.x += ACCEL;
     };
 
+    // Integrate velocity, apply drag, and clamp to top speed
+    game_state.velocity += game_state.acceleration * engine.delta_f32;
+    game_state.velocity *= (1.0 - DRAG * engine.delta_f32).max(0.0);
+    if game_state.velocity.length() > MAX_SPEED {
+        game_state.velocity = game_state.velocity.normalize() * MAX_SPEED;
+    }
+    if game_state.velocity.length() < VELOCITY_EPSILON {
+        game_state.velocity = Vec2::ZERO;
+    }
+
+    let player = engine.sprites.get_mut("player").unwrap();
+    player.translation += game_state.velocity * engine.delta_f32;
+    if game_state.velocity != Vec2::ZERO {
+        player.rotation = game_state.velocity.y.atan2(game_state.velocity.x);
+    }
+    trace!(target: "game::input", "Player position: {:?}", player.translation);
+
     // Handle Mouse input
-    let ferris_sprite = ASSETS.join("happy_ferris.png").canonicalize().unwrap();
+    let ferris_sprite = assets::resolve("happy_ferris.png");
     if engine.mouse_state.just_pressed(MouseButton::Left) {
         if let Some(mouse_location) = engine.mouse_state.location() {
             let label = format!("ferris_{}", game_state.ferris_index);
             game_state.ferris_index += 1;
-            let ferris = engine.add_sprite(label, ferris_sprite.clone());
+            let ferris = engine.add_sprite(label.clone(), ferris_sprite.clone());
             ferris.translation = mouse_location;
             ferris.scale = 0.5;
             ferris.collision = true;
+            debug!(target: "game::spawn", "Spawned '{label}' at {:?}", ferris.translation);
+            game_state.enemy_label.push(label);
+            game_state.enemy_damage.push(DEFAULT_ENEMY_DAMAGE);
         }
     }
 
@@ -141,11 +294,14 @@ fn game_logic(engine: &mut Engine, game_state: &mut GameState) {
     if game_state.spawn_timer.tick(engine.delta).just_finished() {
         let label = format!("ferris_{}", game_state.ferris_index);
         game_state.ferris_index += 1;
-        let ferris = engine.add_sprite(label, ferris_sprite.clone());
+        let ferris = engine.add_sprite(label.clone(), ferris_sprite.clone());
         ferris.translation.x = rng().random_range(-550.0..550.0);
         ferris.translation.y = rng().random_range(-325.0..325.0);
         ferris.scale = 0.5;
         ferris.collision = true;
+        debug!(target: "game::spawn", "Spawned '{label}' at {:?}", ferris.translation);
+        game_state.enemy_label.push(label);
+        game_state.enemy_damage.push(DEFAULT_ENEMY_DAMAGE);
     }
 
     // Reset score
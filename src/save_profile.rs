@@ -0,0 +1,104 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Persisted player progress, written under `assets/config`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SaveProfile {
+    pub high_score: u32,
+}
+
+// Namespaced by crate name so this example's save file can't clobber another example's
+// when both are run from the same working directory.
+fn save_path() -> PathBuf {
+    PathBuf::from("./assets/config")
+        .join(env!("CARGO_PKG_NAME"))
+        .join("save_profile.json")
+}
+
+/// Load the save profile from disk, defaulting to zero if it is missing or corrupt.
+pub fn load() -> SaveProfile {
+    load_from(&save_path())
+}
+
+fn load_from(path: &Path) -> SaveProfile {
+    match fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(profile) => {
+                info!(target: "game::save", "Loaded save profile from {}", path.display());
+                profile
+            }
+            Err(err) => {
+                warn!(target: "game::save", "Save profile at {} is corrupt ({err}); starting fresh", path.display());
+                SaveProfile::default()
+            }
+        },
+        Err(_) => {
+            debug!(target: "game::save", "No save profile found at {}; starting fresh", path.display());
+            SaveProfile::default()
+        }
+    }
+}
+
+/// Atomically write the save profile (write to a temp file, then rename over the real one)
+/// so a crash mid-save can never leave a corrupt profile behind.
+pub fn save(profile: &SaveProfile) -> io::Result<()> {
+    save_to(&save_path(), profile)
+}
+
+fn save_to(path: &Path, profile: &SaveProfile) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(profile).expect("SaveProfile always serializes");
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)?;
+    info!(target: "game::save", "Saved high score {} to {}", profile.high_score, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ultimate_rust2_save_profile_test_{name}.json"))
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let path = scratch_path("round_trip");
+        let profile = SaveProfile { high_score: 42 };
+
+        save_to(&path, &profile).unwrap();
+        let loaded = load_from(&path);
+
+        assert_eq!(loaded.high_score, 42);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_default_on_corrupt_file() {
+        let path = scratch_path("corrupt");
+        fs::write(&path, "not valid json").unwrap();
+
+        let loaded = load_from(&path);
+
+        assert_eq!(loaded.high_score, 0);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_default_when_missing() {
+        let path = scratch_path("missing");
+        let _ = fs::remove_file(&path);
+
+        let loaded = load_from(&path);
+
+        assert_eq!(loaded.high_score, 0);
+    }
+}